@@ -0,0 +1,371 @@
+//! Instruction types and client-side builders for the data-store program.
+
+use crate::state::Authority;
+use solana_program::{
+    instruction::{AccountMeta, Instruction as SolanaInstruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+
+/// Leading discriminator bytes, one per [`Instruction`] variant.
+const TAG_STORE: u8 = 0;
+const TAG_WRITE_CHUNK: u8 = 1;
+const TAG_APPEND: u8 = 2;
+const TAG_TRUNCATE: u8 = 3;
+const TAG_CLOSE: u8 = 4;
+const TAG_SET_AUTHORITY: u8 = 5;
+const TAG_WITHDRAW: u8 = 6;
+
+/// Maximum length of the `label` seed that namespaces a user's records.
+pub const MAX_LABEL_LEN: usize = 32;
+
+/// Instructions supported by the data-store program. Mirrors the style of
+/// `system_instruction`: a discriminator byte, a length-prefixed `label`
+/// seed that namespaces the record, and then variant-specific fields.
+pub enum Instruction<'a> {
+    /// Overwrite the labeled record with `data`.
+    Store { label: &'a [u8], data: &'a [u8] },
+    /// Write `bytes` at `offset`, growing the record if needed.
+    WriteChunk {
+        label: &'a [u8],
+        offset: u64,
+        bytes: &'a [u8],
+    },
+    /// Append `bytes` to the end of the record.
+    Append { label: &'a [u8], bytes: &'a [u8] },
+    /// Shrink the record to `new_len` bytes, refunding the freed rent.
+    Truncate { label: &'a [u8], new_len: u64 },
+    /// Close the record, returning all lamports to the user.
+    Close { label: &'a [u8] },
+    /// Rotate the record's authority, e.g. to a different owner or to a
+    /// multisig.
+    SetAuthority {
+        label: &'a [u8],
+        new_authority: Authority,
+    },
+    /// Withdraw `lamports` of surplus balance, never dipping below the
+    /// record's stored rent-exempt reserve.
+    Withdraw { label: &'a [u8], lamports: u64 },
+}
+
+impl<'a> Instruction<'a> {
+    /// The `label` seed carried by every variant, used to derive the PDA.
+    pub fn label(&self) -> &'a [u8] {
+        match *self {
+            Instruction::Store { label, .. }
+            | Instruction::WriteChunk { label, .. }
+            | Instruction::Append { label, .. }
+            | Instruction::Truncate { label, .. }
+            | Instruction::Close { label }
+            | Instruction::SetAuthority { label, .. }
+            | Instruction::Withdraw { label, .. } => label,
+        }
+    }
+
+    /// Parses the leading discriminator byte and length-prefixed label, then
+    /// decodes the rest of `data` according to the discriminator.
+    pub fn unpack(data: &'a [u8]) -> Result<Self, ProgramError> {
+        let (&tag, rest) = data
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        let (&label_len, rest) = rest
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        let label_len = label_len as usize;
+        if label_len == 0 || label_len > MAX_LABEL_LEN || rest.len() < label_len {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let (label, rest) = rest.split_at(label_len);
+
+        Ok(match tag {
+            TAG_STORE => Instruction::Store { label, data: rest },
+            TAG_WRITE_CHUNK => {
+                if rest.len() < 8 {
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+                let (offset_bytes, bytes) = rest.split_at(8);
+                Instruction::WriteChunk {
+                    label,
+                    offset: u64::from_le_bytes(offset_bytes.try_into().unwrap()),
+                    bytes,
+                }
+            }
+            TAG_APPEND => Instruction::Append { label, bytes: rest },
+            TAG_TRUNCATE => {
+                if rest.len() != 8 {
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+                Instruction::Truncate {
+                    label,
+                    new_len: u64::from_le_bytes(rest.try_into().unwrap()),
+                }
+            }
+            TAG_CLOSE => {
+                if !rest.is_empty() {
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+                Instruction::Close { label }
+            }
+            TAG_SET_AUTHORITY => {
+                let (new_authority, used) = Authority::unpack(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                if used != rest.len() {
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+                Instruction::SetAuthority { label, new_authority }
+            }
+            TAG_WITHDRAW => {
+                if rest.len() != 8 {
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+                Instruction::Withdraw {
+                    label,
+                    lamports: u64::from_le_bytes(rest.try_into().unwrap()),
+                }
+            }
+            _ => return Err(ProgramError::InvalidInstructionData),
+        })
+    }
+}
+
+/// Derives the PDA that stores `user`'s `label`-namespaced record for this
+/// program, mirroring the multi-seed pattern the system program docs show
+/// for `find_program_address`.
+pub fn derive_data_address(program_id: &Pubkey, user: &Pubkey, label: &[u8]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[user.as_ref(), label], program_id)
+}
+
+/// Builds the fixed account list every instruction shares (user, data PDA,
+/// system program, rent sysvar), followed by one readonly signer meta per
+/// entry in `extra_signers` for authorizing a multisig record.
+fn account_metas(user: &Pubkey, data_account: &Pubkey, extra_signers: &[Pubkey]) -> Vec<AccountMeta> {
+    let mut metas = vec![
+        AccountMeta::new(*user, true),
+        AccountMeta::new(*data_account, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(sysvar::rent::ID, false),
+    ];
+    metas.extend(
+        extra_signers
+            .iter()
+            .map(|signer| AccountMeta::new_readonly(*signer, true)),
+    );
+    metas
+}
+
+fn pack_header(tag: u8, label: &[u8], extra_capacity: usize) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(2 + label.len() + extra_capacity);
+    payload.push(tag);
+    payload.push(label.len() as u8);
+    payload.extend_from_slice(label);
+    payload
+}
+
+/// Builds a `Store` instruction that overwrites the labeled record with
+/// `data`.
+pub fn store(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    label: &[u8],
+    data: &[u8],
+    extra_signers: &[Pubkey],
+) -> SolanaInstruction {
+    let (data_account, _) = derive_data_address(program_id, user, label);
+    let mut payload = pack_header(TAG_STORE, label, data.len());
+    payload.extend_from_slice(data);
+    SolanaInstruction::new_with_bytes(
+        *program_id,
+        &payload,
+        account_metas(user, &data_account, extra_signers),
+    )
+}
+
+/// Builds a `WriteChunk` instruction that writes `bytes` at `offset`,
+/// growing the record if needed.
+pub fn write_chunk(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    label: &[u8],
+    offset: u64,
+    bytes: &[u8],
+    extra_signers: &[Pubkey],
+) -> SolanaInstruction {
+    let (data_account, _) = derive_data_address(program_id, user, label);
+    let mut payload = pack_header(TAG_WRITE_CHUNK, label, 8 + bytes.len());
+    payload.extend_from_slice(&offset.to_le_bytes());
+    payload.extend_from_slice(bytes);
+    SolanaInstruction::new_with_bytes(
+        *program_id,
+        &payload,
+        account_metas(user, &data_account, extra_signers),
+    )
+}
+
+/// Builds an `Append` instruction that appends `bytes` to the end of the
+/// record.
+pub fn append(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    label: &[u8],
+    bytes: &[u8],
+    extra_signers: &[Pubkey],
+) -> SolanaInstruction {
+    let (data_account, _) = derive_data_address(program_id, user, label);
+    let mut payload = pack_header(TAG_APPEND, label, bytes.len());
+    payload.extend_from_slice(bytes);
+    SolanaInstruction::new_with_bytes(
+        *program_id,
+        &payload,
+        account_metas(user, &data_account, extra_signers),
+    )
+}
+
+/// Builds a `Truncate` instruction that shrinks the record to `new_len`
+/// bytes, refunding the freed rent.
+pub fn truncate(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    label: &[u8],
+    new_len: u64,
+    extra_signers: &[Pubkey],
+) -> SolanaInstruction {
+    let (data_account, _) = derive_data_address(program_id, user, label);
+    let mut payload = pack_header(TAG_TRUNCATE, label, 8);
+    payload.extend_from_slice(&new_len.to_le_bytes());
+    SolanaInstruction::new_with_bytes(
+        *program_id,
+        &payload,
+        account_metas(user, &data_account, extra_signers),
+    )
+}
+
+/// Builds a `Close` instruction that reclaims the labeled record's PDA and
+/// all of its lamports.
+pub fn close(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    label: &[u8],
+    extra_signers: &[Pubkey],
+) -> SolanaInstruction {
+    let (data_account, _) = derive_data_address(program_id, user, label);
+    let payload = pack_header(TAG_CLOSE, label, 0);
+    SolanaInstruction::new_with_bytes(
+        *program_id,
+        &payload,
+        account_metas(user, &data_account, extra_signers),
+    )
+}
+
+/// Builds a `Withdraw` instruction that pulls `lamports` of surplus balance
+/// out of the record without dipping below its rent-exempt reserve.
+pub fn withdraw(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    label: &[u8],
+    lamports: u64,
+    extra_signers: &[Pubkey],
+) -> SolanaInstruction {
+    let (data_account, _) = derive_data_address(program_id, user, label);
+    let mut payload = pack_header(TAG_WITHDRAW, label, 8);
+    payload.extend_from_slice(&lamports.to_le_bytes());
+    SolanaInstruction::new_with_bytes(
+        *program_id,
+        &payload,
+        account_metas(user, &data_account, extra_signers),
+    )
+}
+
+/// Builds a `SetAuthority` instruction that rotates the record's authority
+/// to `new_authority`, e.g. swapping a single owner for a multisig.
+pub fn set_authority(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    label: &[u8],
+    new_authority: &Authority,
+    extra_signers: &[Pubkey],
+) -> SolanaInstruction {
+    let (data_account, _) = derive_data_address(program_id, user, label);
+    let mut payload = pack_header(TAG_SET_AUTHORITY, label, new_authority.packed_len());
+    new_authority.pack(&mut payload);
+    SolanaInstruction::new_with_bytes(
+        *program_id,
+        &payload,
+        account_metas(user, &data_account, extra_signers),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpack_rejects_an_unknown_tag() {
+        let data = [0xff, 1, b'a'];
+        assert!(Instruction::unpack(&data).is_err());
+    }
+
+    #[test]
+    fn unpack_rejects_a_truncated_write_chunk_payload() {
+        // Tag, label "a", then only 3 of the required 8 offset bytes.
+        let data = [TAG_WRITE_CHUNK, 1, b'a', 0, 0, 0];
+        assert!(Instruction::unpack(&data).is_err());
+    }
+
+    #[test]
+    fn unpack_rejects_a_truncated_truncate_payload() {
+        // Tag, label "a", then only 4 of the required 8 new_len bytes.
+        let data = [TAG_TRUNCATE, 1, b'a', 0, 0, 0, 0];
+        assert!(Instruction::unpack(&data).is_err());
+    }
+
+    #[test]
+    fn unpack_rejects_a_truncated_withdraw_payload() {
+        // Tag, label "a", then only 4 of the required 8 lamports bytes.
+        let data = [TAG_WITHDRAW, 1, b'a', 0, 0, 0, 0];
+        assert!(Instruction::unpack(&data).is_err());
+    }
+
+    #[test]
+    fn unpack_rejects_a_zero_length_label() {
+        let data = [TAG_STORE, 0];
+        assert!(Instruction::unpack(&data).is_err());
+    }
+
+    #[test]
+    fn unpack_rejects_a_label_over_the_max_length() {
+        let label = vec![b'a'; MAX_LABEL_LEN + 1];
+        let mut data = vec![TAG_STORE, label.len() as u8];
+        data.extend_from_slice(&label);
+        assert!(Instruction::unpack(&data).is_err());
+    }
+
+    #[test]
+    fn unpack_accepts_a_label_at_the_max_length() {
+        let label = vec![b'a'; MAX_LABEL_LEN];
+        let mut data = vec![TAG_STORE, label.len() as u8];
+        data.extend_from_slice(&label);
+        assert!(Instruction::unpack(&data).is_ok());
+    }
+
+    #[test]
+    fn different_labels_for_the_same_user_derive_distinct_pdas() {
+        let program_id = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+
+        let (first, _) = derive_data_address(&program_id, &user, b"label-one");
+        let (second, _) = derive_data_address(&program_id, &user, b"label-two");
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn the_same_user_and_label_derive_the_same_pda() {
+        let program_id = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+
+        let (first, first_bump) = derive_data_address(&program_id, &user, b"label");
+        let (second, second_bump) = derive_data_address(&program_id, &user, b"label");
+        assert_eq!(first, second);
+        assert_eq!(first_bump, second_bump);
+    }
+}
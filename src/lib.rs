@@ -2,6 +2,10 @@
 // system_instruction 已弃用，官方建议用 solana_system_interface；但该 crate 与 solana-program 2 类型不兼容（Address vs Pubkey、Instruction 版本不同），故仍使用此处 API 并屏蔽警告
 #![allow(deprecated)]
 
+pub mod instruction;
+pub mod state;
+
+use instruction::Instruction;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
@@ -13,24 +17,24 @@ use solana_program::{
     system_instruction,
     sysvar::{self, Sysvar},
 };
+use state::{Authority, Header};
 
 solana_program::entrypoint!(process_instruction);
 
-/// Maximum allowed data size (10 KB)
+/// Maximum allowed size for a single `Store` instruction's payload (10 KB)
 const MAX_DATA_SIZE: usize = 10 * 1024;
 
+/// Maximum allowed size for the account as a whole (header plus payload)
+/// once assembled from chunked writes or appends (256 KB)
+pub const MAX_ACCOUNT_SIZE: usize = 256 * 1024;
+
 pub fn process_instruction(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     data: &[u8],
 ) -> ProgramResult {
-    // Security: Validate input data size to prevent DoS attacks
-    if data.len() > MAX_DATA_SIZE {
-        return Err(ProgramError::InvalidInstructionData);
-    }
-
     let accounts_iter = &mut accounts.iter();
-    
+
     // Extract accounts
     let user_account = next_account_info(accounts_iter)?;
     let data_account = next_account_info(accounts_iter)?;
@@ -60,19 +64,284 @@ pub fn process_instruction(
         return Err(ProgramError::IncorrectProgramId);
     }
 
-    // Calculate rent exemption for the data size
-    let rent_exemption = Rent::get()?.minimum_balance(data.len());
-    
-    // Derive PDA and verify it matches the provided data_account
+    // Any remaining accounts are extra co-signers for a multisig authority
+    let mut signer_keys: Vec<&Pubkey> = vec![user_account.key];
+    for extra_account in accounts_iter {
+        if extra_account.is_signer {
+            signer_keys.push(extra_account.key);
+        }
+    }
+
+    let instruction = Instruction::unpack(data)?;
+    let label = instruction.label();
+
+    // Derive the label-namespaced PDA and verify it matches the provided
+    // data_account
     let (pda, bump_seed) =
-        Pubkey::find_program_address(&[user_account.key.as_ref()], program_id);
+        Pubkey::find_program_address(&[user_account.key.as_ref(), label], program_id);
 
     if pda != *data_account.key {
         return Err(ProgramError::InvalidSeeds);
     }
 
     // Prepare signer seeds for PDA operations
-    let signer_seeds: &[&[u8]] = &[user_account.key.as_ref(), std::slice::from_ref(&bump_seed)];
+    let signer_seeds: &[&[u8]] = &[
+        user_account.key.as_ref(),
+        label,
+        std::slice::from_ref(&bump_seed),
+    ];
+
+    // Read the header already stored in the account, if any, and require its
+    // authority to authorize this instruction. A brand-new account has no
+    // authority yet, so whoever creates it becomes the owner.
+    let existing_header: Option<(Header, usize)> = if data_account.lamports() == 0 {
+        None
+    } else {
+        let account_data = data_account.try_borrow_data()?;
+        Some(Header::unpack(&account_data)?)
+    };
+
+    if let Some((header, _)) = &existing_header {
+        if !header.authority.is_authorized(&signer_keys) {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+    }
+
+    let header_len = match &existing_header {
+        Some((_, header_len)) => *header_len,
+        None => default_header(user_account.key).packed_len(),
+    };
+
+    match instruction {
+        Instruction::Store { data: payload, .. } => {
+            // Security: Validate input data size to prevent DoS attacks
+            if payload.len() > MAX_DATA_SIZE {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+
+            let target_len = header_len
+                .checked_add(payload.len())
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+
+            ensure_account_size_and_rent(
+                program_id,
+                user_account,
+                data_account,
+                accounts,
+                signer_seeds,
+                target_len,
+            )?;
+            update_header(data_account, user_account, &existing_header, target_len)?;
+            data_account.try_borrow_mut_data()?[header_len..].copy_from_slice(payload);
+        }
+        Instruction::WriteChunk { offset, bytes, .. } => {
+            let end = offset
+                .checked_add(bytes.len() as u64)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            let end = end as usize;
+            let offset = offset as usize;
+
+            let total_end = header_len
+                .checked_add(end)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            if total_end > MAX_ACCOUNT_SIZE {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+
+            // Never shrink an existing record just because this chunk is short
+            let current_payload_len = data_account.data_len().saturating_sub(header_len);
+            let target_payload_len = end.max(current_payload_len);
+            let target_len = header_len
+                .checked_add(target_payload_len)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+
+            ensure_account_size_and_rent(
+                program_id,
+                user_account,
+                data_account,
+                accounts,
+                signer_seeds,
+                target_len,
+            )?;
+            update_header(data_account, user_account, &existing_header, target_len)?;
+            let start = header_len + offset;
+            let stop = header_len + end;
+            data_account.try_borrow_mut_data()?[start..stop].copy_from_slice(bytes);
+        }
+        Instruction::Append { bytes, .. } => {
+            let current_payload_len = data_account.data_len().saturating_sub(header_len);
+            let target_payload_len = current_payload_len
+                .checked_add(bytes.len())
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            let target_len = header_len
+                .checked_add(target_payload_len)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            if target_len > MAX_ACCOUNT_SIZE {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+
+            ensure_account_size_and_rent(
+                program_id,
+                user_account,
+                data_account,
+                accounts,
+                signer_seeds,
+                target_len,
+            )?;
+            update_header(data_account, user_account, &existing_header, target_len)?;
+            let start = header_len + current_payload_len;
+            let stop = header_len + target_payload_len;
+            data_account.try_borrow_mut_data()?[start..stop].copy_from_slice(bytes);
+        }
+        Instruction::Truncate { new_len, .. } => {
+            let new_len = new_len as usize;
+            let current_payload_len = data_account.data_len().saturating_sub(header_len);
+            if new_len > current_payload_len {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+
+            let target_len = header_len
+                .checked_add(new_len)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+
+            ensure_account_size_and_rent(
+                program_id,
+                user_account,
+                data_account,
+                accounts,
+                signer_seeds,
+                target_len,
+            )?;
+            update_header(data_account, user_account, &existing_header, target_len)?;
+        }
+        Instruction::Close { .. } => {
+            // Zero the data before handing the account back to the system
+            // program so no stale record contents are left behind.
+            data_account.try_borrow_mut_data()?.fill(0);
+            data_account.resize(0)?;
+
+            // Security: Use the same lamport-transfer pattern as the
+            // excess-rent refund in `ensure_account_size_and_rent` to move
+            // the full balance to the user.
+            let lamports = data_account.lamports();
+            **user_account.lamports.borrow_mut() += lamports;
+            **data_account.lamports.borrow_mut() = 0;
+
+            data_account.assign(&system_program::ID);
+        }
+        Instruction::SetAuthority { new_authority, .. } => {
+            // There's no existing authority to rotate away from if the
+            // record hasn't been created yet.
+            if existing_header.is_none() {
+                return Err(ProgramError::UninitializedAccount);
+            }
+
+            let payload: Vec<u8> = data_account.try_borrow_data()?[header_len..].to_vec();
+            let new_header_len = new_authority.packed_len();
+            let target_len = new_header_len
+                .checked_add(payload.len())
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            if target_len > MAX_ACCOUNT_SIZE {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+
+            ensure_account_size_and_rent(
+                program_id,
+                user_account,
+                data_account,
+                accounts,
+                signer_seeds,
+                target_len,
+            )?;
+            let rent_exempt_reserve = Rent::get()?.minimum_balance(target_len);
+            write_header(
+                data_account,
+                &Header {
+                    rent_exempt_reserve,
+                    authority: new_authority,
+                },
+            )?;
+            data_account.try_borrow_mut_data()?[new_header_len..].copy_from_slice(&payload);
+        }
+        Instruction::Withdraw { lamports, .. } => {
+            let (header, _) = existing_header.ok_or(ProgramError::UninitializedAccount)?;
+
+            // Security: Use checked arithmetic to prevent underflow, and
+            // never let the balance drop below the rent-exempt reserve
+            let remaining = data_account
+                .lamports()
+                .checked_sub(lamports)
+                .ok_or(ProgramError::InsufficientFunds)?;
+            if remaining < header.rent_exempt_reserve {
+                return Err(ProgramError::InsufficientFunds);
+            }
+
+            **data_account.lamports.borrow_mut() = remaining;
+            **user_account.lamports.borrow_mut() += lamports;
+        }
+    }
+
+    Ok(())
+}
+
+/// Packs `header` and writes it into the front of `data_account`'s data.
+fn write_header(data_account: &AccountInfo, header: &Header) -> ProgramResult {
+    let header_len = header.packed_len();
+    let mut header_bytes = Vec::with_capacity(header_len);
+    header.pack(&mut header_bytes);
+    data_account.try_borrow_mut_data()?[..header_len].copy_from_slice(&header_bytes);
+    Ok(())
+}
+
+/// The header a brand-new account is initialized with: owned solely by its
+/// creator, with a placeholder reserve that gets filled in once the target
+/// size is known.
+fn default_header(owner: &Pubkey) -> Header {
+    Header {
+        rent_exempt_reserve: 0,
+        authority: Authority::Single(*owner),
+    }
+}
+
+/// Keeps a record's header in sync with its current size: writes a brand
+/// new header for an account created by this instruction, or otherwise just
+/// refreshes the stored `rent_exempt_reserve` for the now-`target_len`
+/// account, leaving its authority untouched.
+fn update_header(
+    data_account: &AccountInfo,
+    user_account: &AccountInfo,
+    existing_header: &Option<(Header, usize)>,
+    target_len: usize,
+) -> ProgramResult {
+    let rent_exempt_reserve = Rent::get()?.minimum_balance(target_len);
+    match existing_header {
+        Some(_) => {
+            data_account.try_borrow_mut_data()?[..8]
+                .copy_from_slice(&rent_exempt_reserve.to_le_bytes());
+            Ok(())
+        }
+        None => write_header(
+            data_account,
+            &Header {
+                rent_exempt_reserve,
+                ..default_header(user_account.key)
+            },
+        ),
+    }
+}
+
+/// Creates the PDA if it doesn't exist yet, otherwise tops up or refunds rent
+/// and resizes it, so that it ends up exactly `target_len` bytes and
+/// rent-exempt for that size.
+fn ensure_account_size_and_rent<'a>(
+    program_id: &Pubkey,
+    user_account: &AccountInfo<'a>,
+    data_account: &AccountInfo<'a>,
+    accounts: &[AccountInfo<'a>],
+    signer_seeds: &[&[u8]],
+    target_len: usize,
+) -> ProgramResult {
+    let rent_exemption = Rent::get()?.minimum_balance(target_len);
 
     // If account doesn't exist, create it
     if data_account.lamports() == 0 {
@@ -81,13 +350,12 @@ pub fn process_instruction(
                 user_account.key,
                 data_account.key,
                 rent_exemption,
-                data.len() as u64,
+                target_len as u64,
                 program_id,
             ),
             accounts,
             &[signer_seeds],
         )?;
-        data_account.try_borrow_mut_data()?.copy_from_slice(data);
         return Ok(());
     }
 
@@ -97,7 +365,7 @@ pub fn process_instruction(
         let additional_lamports = rent_exemption
             .checked_sub(data_account.lamports())
             .ok_or(ProgramError::ArithmeticOverflow)?;
-        
+
         solana_program::program::invoke(
             &system_instruction::transfer(
                 user_account.key,
@@ -115,15 +383,13 @@ pub fn process_instruction(
             .lamports()
             .checked_sub(rent_exemption)
             .ok_or(ProgramError::ArithmeticOverflow)?;
-        
+
         **user_account.lamports.borrow_mut() += excess;
         **data_account.lamports.borrow_mut() = rent_exemption;
     }
 
-    // Resize account if needed and update data
-    data_account.resize(data.len())?;
-    data_account.try_borrow_mut_data()?.copy_from_slice(data);
+    // Resize account if needed
+    data_account.resize(target_len)?;
 
     Ok(())
 }
-
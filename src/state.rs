@@ -0,0 +1,243 @@
+//! On-account state: the authority header stored at the front of every
+//! record, borrowing the owner/multisig model from the SPL token processor.
+
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+/// Maximum number of signers in a multisig authority, matching the cap the
+/// SPL token program uses for its own `Multisig` accounts.
+pub const MAX_SIGNERS: usize = 11;
+
+const TAG_SINGLE: u8 = 0;
+const TAG_MULTISIG: u8 = 1;
+
+const PUBKEY_LEN: usize = 32;
+
+/// Who is allowed to authorize mutations to a record.
+pub enum Authority {
+    /// A single pubkey must sign.
+    Single(Pubkey),
+    /// At least `m` of `signers` must sign.
+    Multisig { m: u8, signers: Vec<Pubkey> },
+}
+
+impl Authority {
+    /// Size in bytes this authority occupies once packed into the account
+    /// header.
+    pub fn packed_len(&self) -> usize {
+        match self {
+            Authority::Single(_) => 1 + PUBKEY_LEN,
+            Authority::Multisig { signers, .. } => 1 + 1 + 1 + PUBKEY_LEN * signers.len(),
+        }
+    }
+
+    /// Appends the packed header representation of this authority to `out`.
+    pub fn pack(&self, out: &mut Vec<u8>) {
+        match self {
+            Authority::Single(owner) => {
+                out.push(TAG_SINGLE);
+                out.extend_from_slice(owner.as_ref());
+            }
+            Authority::Multisig { m, signers } => {
+                out.push(TAG_MULTISIG);
+                out.push(*m);
+                out.push(signers.len() as u8);
+                for signer in signers {
+                    out.extend_from_slice(signer.as_ref());
+                }
+            }
+        }
+    }
+
+    /// Unpacks an authority header from the front of `data`, returning it
+    /// along with the number of bytes it occupied.
+    pub fn unpack(data: &[u8]) -> Result<(Self, usize), ProgramError> {
+        let (&tag, rest) = data
+            .split_first()
+            .ok_or(ProgramError::InvalidAccountData)?;
+        match tag {
+            TAG_SINGLE => {
+                if rest.len() < PUBKEY_LEN {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+                let owner = Pubkey::try_from(&rest[..PUBKEY_LEN])
+                    .map_err(|_| ProgramError::InvalidAccountData)?;
+                Ok((Authority::Single(owner), 1 + PUBKEY_LEN))
+            }
+            TAG_MULTISIG => {
+                let (&m, rest) = rest
+                    .split_first()
+                    .ok_or(ProgramError::InvalidAccountData)?;
+                let (&n, rest) = rest
+                    .split_first()
+                    .ok_or(ProgramError::InvalidAccountData)?;
+                let n = n as usize;
+                if n == 0 || n > MAX_SIGNERS || m == 0 || m as usize > n {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+                if rest.len() < PUBKEY_LEN * n {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+                let mut signers = Vec::with_capacity(n);
+                for chunk in rest[..PUBKEY_LEN * n].chunks_exact(PUBKEY_LEN) {
+                    signers.push(
+                        Pubkey::try_from(chunk).map_err(|_| ProgramError::InvalidAccountData)?,
+                    );
+                }
+                Ok((Authority::Multisig { m, signers }, 1 + 1 + 1 + PUBKEY_LEN * n))
+            }
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+
+    /// Returns true if enough of `signer_keys` authorize an action under
+    /// this authority (a single matching signer, or at least `m` of the
+    /// multisig's `signers`).
+    pub fn is_authorized(&self, signer_keys: &[&Pubkey]) -> bool {
+        match self {
+            Authority::Single(owner) => signer_keys.contains(&owner),
+            Authority::Multisig { m, signers } => {
+                let matched = signers
+                    .iter()
+                    .filter(|signer| signer_keys.contains(signer))
+                    .count();
+                matched >= *m as usize
+            }
+        }
+    }
+}
+
+/// Size in bytes of the explicitly stored `rent_exempt_reserve` field.
+const RESERVE_LEN: usize = 8;
+
+/// The header stored at the front of every record: the authority allowed to
+/// mutate it, and the lamport balance it must keep to stay rent-exempt.
+/// Storing the reserve explicitly (rather than recomputing it from the
+/// account's current size) mirrors the SPL token program's
+/// `rent_exempt_reserve`, and lets `Withdraw` check against it directly.
+pub struct Header {
+    pub rent_exempt_reserve: u64,
+    pub authority: Authority,
+}
+
+impl Header {
+    /// Size in bytes this header occupies at the front of the account.
+    pub fn packed_len(&self) -> usize {
+        RESERVE_LEN + self.authority.packed_len()
+    }
+
+    /// Appends the packed representation of this header to `out`.
+    pub fn pack(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.rent_exempt_reserve.to_le_bytes());
+        self.authority.pack(out);
+    }
+
+    /// Unpacks a header from the front of `data`, returning it along with
+    /// the number of bytes it occupied.
+    pub fn unpack(data: &[u8]) -> Result<(Self, usize), ProgramError> {
+        if data.len() < RESERVE_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let (reserve_bytes, rest) = data.split_at(RESERVE_LEN);
+        let rent_exempt_reserve = u64::from_le_bytes(reserve_bytes.try_into().unwrap());
+        let (authority, authority_len) = Authority::unpack(rest)?;
+        Ok((
+            Header {
+                rent_exempt_reserve,
+                authority,
+            },
+            RESERVE_LEN + authority_len,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pubkey(byte: u8) -> Pubkey {
+        Pubkey::new_from_array([byte; 32])
+    }
+
+    #[test]
+    fn single_authority_round_trips() {
+        let owner = pubkey(1);
+        let authority = Authority::Single(owner);
+        let mut bytes = Vec::new();
+        authority.pack(&mut bytes);
+        assert_eq!(bytes.len(), authority.packed_len());
+
+        let (unpacked, used) = Authority::unpack(&bytes).unwrap();
+        assert_eq!(used, bytes.len());
+        match unpacked {
+            Authority::Single(key) => assert_eq!(key, owner),
+            Authority::Multisig { .. } => panic!("expected Single"),
+        }
+    }
+
+    #[test]
+    fn multisig_authority_round_trips() {
+        let signers = vec![pubkey(1), pubkey(2), pubkey(3)];
+        let authority = Authority::Multisig { m: 2, signers: signers.clone() };
+        let mut bytes = Vec::new();
+        authority.pack(&mut bytes);
+        assert_eq!(bytes.len(), authority.packed_len());
+
+        let (unpacked, used) = Authority::unpack(&bytes).unwrap();
+        assert_eq!(used, bytes.len());
+        match unpacked {
+            Authority::Multisig { m, signers: unpacked_signers } => {
+                assert_eq!(m, 2);
+                assert_eq!(unpacked_signers, signers);
+            }
+            Authority::Single(_) => panic!("expected Multisig"),
+        }
+    }
+
+    #[test]
+    fn multisig_rejects_m_greater_than_n() {
+        let mut bytes = vec![TAG_MULTISIG, 3, 2];
+        bytes.extend_from_slice(pubkey(1).as_ref());
+        bytes.extend_from_slice(pubkey(2).as_ref());
+        assert!(Authority::unpack(&bytes).is_err());
+    }
+
+    #[test]
+    fn single_authority_is_authorized_only_for_owner() {
+        let owner = pubkey(1);
+        let other = pubkey(2);
+        let authority = Authority::Single(owner);
+
+        assert!(authority.is_authorized(&[&owner]));
+        assert!(!authority.is_authorized(&[&other]));
+    }
+
+    #[test]
+    fn multisig_requires_m_of_n_signers() {
+        let signers = vec![pubkey(1), pubkey(2), pubkey(3)];
+        let authority = Authority::Multisig { m: 2, signers: signers.clone() };
+
+        assert!(!authority.is_authorized(&[&signers[0]]));
+        assert!(authority.is_authorized(&[&signers[0], &signers[1]]));
+        assert!(authority.is_authorized(&[&signers[0], &signers[1], &signers[2]]));
+        assert!(!authority.is_authorized(&[&pubkey(9), &pubkey(10)]));
+    }
+
+    #[test]
+    fn header_round_trips_with_reserve() {
+        let header = Header {
+            rent_exempt_reserve: 123_456,
+            authority: Authority::Single(pubkey(7)),
+        };
+        let mut bytes = Vec::new();
+        header.pack(&mut bytes);
+        assert_eq!(bytes.len(), header.packed_len());
+
+        let (unpacked, used) = Header::unpack(&bytes).unwrap();
+        assert_eq!(used, bytes.len());
+        assert_eq!(unpacked.rent_exempt_reserve, 123_456);
+        match unpacked.authority {
+            Authority::Single(key) => assert_eq!(key, pubkey(7)),
+            Authority::Multisig { .. } => panic!("expected Single"),
+        }
+    }
+}
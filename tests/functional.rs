@@ -0,0 +1,256 @@
+//! Process-level tests covering multisig authority rotation, the
+//! reserve-aware `Withdraw` boundary, chunked `WriteChunk` writes and
+//! `Close`, run through `solana-program-test` rather than as unit tests
+//! since all of them exercise `process_instruction`
+//! end-to-end (account creation, CPI to the system program, and rent).
+
+use sol_ss::{instruction, state::Authority};
+use solana_program::pubkey::Pubkey;
+use solana_program_test::{processor, ProgramTest};
+use solana_sdk::{signature::Keypair, signature::Signer, transaction::Transaction};
+
+const LABEL: &[u8] = b"test-record";
+
+fn program_test() -> (Pubkey, ProgramTest) {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new(
+        "sol_ss",
+        program_id,
+        processor!(sol_ss::process_instruction),
+    );
+    (program_id, program_test)
+}
+
+#[tokio::test]
+async fn set_authority_rotates_to_multisig_and_requires_m_of_n() {
+    let (program_id, program_test) = program_test();
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    // Create the record, implicitly owned by `payer`.
+    let store_ix = instruction::store(&program_id, &payer.pubkey(), LABEL, b"hello", &[]);
+    let tx = Transaction::new_signed_with_payer(
+        &[store_ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    // Rotate to a 2-of-3 multisig.
+    let cosigner_a = Keypair::new();
+    let cosigner_b = Keypair::new();
+    let cosigner_c = Keypair::new();
+    let multisig = Authority::Multisig {
+        m: 2,
+        signers: vec![
+            cosigner_a.pubkey(),
+            cosigner_b.pubkey(),
+            cosigner_c.pubkey(),
+        ],
+    };
+    let set_authority_ix =
+        instruction::set_authority(&program_id, &payer.pubkey(), LABEL, &multisig, &[]);
+    let tx = Transaction::new_signed_with_payer(
+        &[set_authority_ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    // A single cosigner is not enough once the multisig requires 2-of-3.
+    let under_signed_ix = instruction::store(
+        &program_id,
+        &payer.pubkey(),
+        LABEL,
+        b"nope",
+        &[cosigner_a.pubkey()],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[under_signed_ix],
+        Some(&payer.pubkey()),
+        &[&payer, &cosigner_a],
+        recent_blockhash,
+    );
+    assert!(banks_client.process_transaction(tx).await.is_err());
+
+    // Two of the three cosigners authorize the mutation.
+    let properly_signed_ix = instruction::store(
+        &program_id,
+        &payer.pubkey(),
+        LABEL,
+        b"updated",
+        &[cosigner_a.pubkey(), cosigner_b.pubkey()],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[properly_signed_ix],
+        Some(&payer.pubkey()),
+        &[&payer, &cosigner_a, &cosigner_b],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+}
+
+#[tokio::test]
+async fn withdraw_stops_exactly_at_the_rent_exempt_reserve() {
+    let (program_id, program_test) = program_test();
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let store_ix = instruction::store(&program_id, &payer.pubkey(), LABEL, b"hello world", &[]);
+    let tx = Transaction::new_signed_with_payer(
+        &[store_ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let (data_account, _) = instruction::derive_data_address(&program_id, &payer.pubkey(), LABEL);
+    let account = banks_client
+        .get_account(data_account)
+        .await
+        .unwrap()
+        .unwrap();
+    let surplus = account.lamports - solana_program::rent::Rent::default().minimum_balance(account.data.len());
+
+    // Withdrawing the full surplus leaves the account exactly rent-exempt.
+    let withdraw_ix =
+        instruction::withdraw(&program_id, &payer.pubkey(), LABEL, surplus, &[]);
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    // Withdrawing even one more lamport would dip below the reserve.
+    let overdraw_ix = instruction::withdraw(&program_id, &payer.pubkey(), LABEL, 1, &[]);
+    let tx = Transaction::new_signed_with_payer(
+        &[overdraw_ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    assert!(banks_client.process_transaction(tx).await.is_err());
+}
+
+#[tokio::test]
+async fn write_chunk_assembles_a_record_from_out_of_order_chunks() {
+    let (program_id, program_test) = program_test();
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    // Write the second half first; this creates the account sized to fit it.
+    let second_half_ix =
+        instruction::write_chunk(&program_id, &payer.pubkey(), LABEL, 6, b"world", &[]);
+    let tx = Transaction::new_signed_with_payer(
+        &[second_half_ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    // Now backfill the first half; it must not shrink or otherwise disturb
+    // the bytes already written by the earlier chunk.
+    let first_half_ix =
+        instruction::write_chunk(&program_id, &payer.pubkey(), LABEL, 0, b"hello,", &[]);
+    let tx = Transaction::new_signed_with_payer(
+        &[first_half_ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let (data_account, _) = instruction::derive_data_address(&program_id, &payer.pubkey(), LABEL);
+    let account = banks_client
+        .get_account(data_account)
+        .await
+        .unwrap()
+        .unwrap();
+    let (_, header_len) = sol_ss::state::Header::unpack(&account.data).unwrap();
+    assert_eq!(&account.data[header_len..], b"hello,world");
+}
+
+#[tokio::test]
+async fn write_chunk_rejects_an_offset_that_overflows_u64() {
+    let (program_id, program_test) = program_test();
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let overflowing_ix =
+        instruction::write_chunk(&program_id, &payer.pubkey(), LABEL, u64::MAX, b"x", &[]);
+    let tx = Transaction::new_signed_with_payer(
+        &[overflowing_ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    assert!(banks_client.process_transaction(tx).await.is_err());
+}
+
+#[tokio::test]
+async fn write_chunk_rejects_a_chunk_past_the_max_account_size() {
+    let (program_id, program_test) = program_test();
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let too_large_ix = instruction::write_chunk(
+        &program_id,
+        &payer.pubkey(),
+        LABEL,
+        sol_ss::MAX_ACCOUNT_SIZE as u64,
+        b"x",
+        &[],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[too_large_ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    assert!(banks_client.process_transaction(tx).await.is_err());
+}
+
+#[tokio::test]
+async fn close_zeroes_the_pda_and_returns_lamports_to_the_user() {
+    let (program_id, program_test) = program_test();
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let store_ix = instruction::store(&program_id, &payer.pubkey(), LABEL, b"hello", &[]);
+    let tx = Transaction::new_signed_with_payer(
+        &[store_ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let (data_account, _) = instruction::derive_data_address(&program_id, &payer.pubkey(), LABEL);
+    let reclaimable_balance = banks_client.get_balance(data_account).await.unwrap();
+    let payer_balance_before_close = banks_client.get_balance(payer.pubkey()).await.unwrap();
+    assert!(reclaimable_balance > 0);
+
+    let close_ix = instruction::close(&program_id, &payer.pubkey(), LABEL, &[]);
+    let tx = Transaction::new_signed_with_payer(
+        &[close_ix],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    // The PDA is fully reclaimed: no lamports left, reassigned to the system
+    // program. A zero-lamport account may simply be purged by the runtime,
+    // so treat "gone" the same as "present with lamports == 0".
+    match banks_client.get_account(data_account).await.unwrap() {
+        None => {}
+        Some(account) => {
+            assert_eq!(account.lamports, 0);
+            assert_eq!(account.owner, solana_program::system_program::ID);
+        }
+    }
+
+    // The user received the reclaimed balance, net of the transaction fee.
+    let payer_balance_after_close = banks_client.get_balance(payer.pubkey()).await.unwrap();
+    assert!(payer_balance_after_close + 10_000 >= payer_balance_before_close + reclaimable_balance);
+}